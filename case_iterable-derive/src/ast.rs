@@ -1,5 +1,5 @@
 /*
-** src/ast.rs
+** case_iterable-derive/src/ast.rs
 **
 ** Copyright (c) 2024 Ian Brault.
 **
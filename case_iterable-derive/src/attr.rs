@@ -0,0 +1,89 @@
+/*
+** case_iterable-derive/src/attr.rs
+**
+** Copyright (c) 2024 Ian Brault.
+**
+** This program is free software: you can redistribute it and/or modify
+** it under the terms of the GNU General Public License as published by
+** the Free Software Foundation, version 3.
+**
+** This program is distributed in the hope that it will be useful, but
+** WITHOUT ANY WARRANTY; without even the implied warranty of
+** MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+** General Public License for more details.
+**
+** You should have received a copy of the GNU General Public License
+** along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+// parses `#[case_iterable(...)]` container and variant attributes
+
+const ATTR_PATH: &str = "case_iterable";
+
+// container-level options, e.g. `#[case_iterable(cyclic)]` on the enum itself
+#[derive(Default)]
+pub struct ContainerAttrs {
+    pub cyclic: bool,
+    pub rename_all_cases: Option<syn::Ident>,
+    pub rename_next: Option<syn::Ident>,
+}
+
+impl ContainerAttrs {
+    pub fn parse(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut result = Self::default();
+        for attr in attrs {
+            if !attr.path().is_ident(ATTR_PATH) {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("cyclic") {
+                    result.cyclic = true;
+                    Ok(())
+                } else if meta.path.is_ident("rename_all_cases") {
+                    result.rename_all_cases = Some(parse_renamed_ident(&meta)?);
+                    Ok(())
+                } else if meta.path.is_ident("rename_next") {
+                    result.rename_next = Some(parse_renamed_ident(&meta)?);
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported case_iterable container attribute"))
+                }
+            })?;
+        }
+        Ok(result)
+    }
+}
+
+// variant-level options, e.g. `#[case_iterable(skip)]` on a single variant
+#[derive(Default)]
+pub struct VariantAttrs {
+    pub skip: bool,
+}
+
+impl VariantAttrs {
+    pub fn parse(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut result = Self::default();
+        for attr in attrs {
+            if !attr.path().is_ident(ATTR_PATH) {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    result.skip = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported case_iterable variant attribute"))
+                }
+            })?;
+        }
+        Ok(result)
+    }
+}
+
+// parses the `= "ident"` half of a `rename_all_cases`/`rename_next` entry
+fn parse_renamed_ident(meta: &syn::meta::ParseNestedMeta) -> syn::Result<syn::Ident> {
+    let value = meta.value()?;
+    let lit: syn::LitStr = value.parse()?;
+    syn::parse_str(&lit.value())
+        .map_err(|_| syn::Error::new(lit.span(), "expected a valid identifier"))
+}
@@ -0,0 +1,488 @@
+/*
+** case_iterable-derive/src/lib.rs
+**
+** Copyright (c) 2024 Ian Brault.
+**
+** This program is free software: you can redistribute it and/or modify
+** it under the terms of the GNU General Public License as published by
+** the Free Software Foundation, version 3.
+**
+** This program is distributed in the hope that it will be useful, but
+** WITHOUT ANY WARRANTY; without even the implied warranty of
+** MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU
+** General Public License for more details.
+**
+** You should have received a copy of the GNU General Public License
+** along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+mod ast;
+mod attr;
+
+use ast::Path;
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::parse_macro_input;
+
+fn gen_iter_struct(ast: &syn::DeriveInput) -> syn::ItemStruct {
+    let enum_name = &ast.ident;
+    let iter_struct_name = ast::Ident::new(&format!("{}Iterator", enum_name));
+
+    let usize_type: syn::Type = ast::Type::path_from_ident(ast::Ident::new("usize")).into();
+    let mut named = syn::punctuated::Punctuated::new();
+    named.push(syn::Field {
+        attrs: Vec::new(),
+        vis: syn::Visibility::Inherited,
+        mutability: syn::FieldMutability::None,
+        ident: Some(ast::Ident::new("front").into()),
+        colon_token: Some(syn::Token![:](Span::call_site())),
+        ty: usize_type.clone(),
+    });
+    named.push(syn::Field {
+        attrs: Vec::new(),
+        vis: syn::Visibility::Inherited,
+        mutability: syn::FieldMutability::None,
+        ident: Some(ast::Ident::new("back").into()),
+        colon_token: Some(syn::Token![:](Span::call_site())),
+        ty: usize_type,
+    });
+    let fields_named = syn::FieldsNamed {
+        brace_token: syn::token::Brace(Span::call_site()),
+        named,
+    };
+    let fields = syn::Fields::Named(fields_named);
+
+    // the enum's own `#[case_iterable(...)]` attribute is consumed by this
+    // derive and isn't registered on the generated iterator struct, so it
+    // must not be carried over (anything else, e.g. doc comments, is fine)
+    let attrs = ast
+        .attrs
+        .iter()
+        .filter(|attr| !attr.path().is_ident("case_iterable"))
+        .cloned()
+        .collect();
+
+    syn::ItemStruct {
+        attrs,
+        struct_token: syn::Token![struct](Span::call_site()),
+        vis: ast.vis.clone(),
+        ident: iter_struct_name.into(),
+        generics: ast.generics.clone(),
+        fields,
+        semi_token: None,
+    }
+}
+
+fn gen_match_arm(
+    enum_ident: &syn::Ident,
+    left: &syn::Ident,
+    right: Option<&syn::Ident>,
+) -> syn::Arm {
+    // format:
+    // Enum::A => Some(Enum::B) if not final
+    // Enum::X => None if final
+
+    let mut enum_path = ast::Path::new();
+    enum_path.push(enum_ident.into());
+    enum_path.push(left.into());
+
+    let pat = syn::Pat::Path(syn::ExprPath {
+        attrs: Vec::new(),
+        qself: None,
+        path: enum_path.into(),
+    });
+    let body = ast::Expr::tokens(if let Some(ident) = right {
+        quote! { Some(#enum_ident::#ident) }
+    } else {
+        quote! { None }
+    });
+    syn::Arm {
+        attrs: Vec::new(),
+        pat,
+        guard: None,
+        fat_arrow_token: syn::Token![=>](Span::call_site()),
+        body: Box::new(body.into()),
+        comma: Some(syn::Token![,](Span::call_site())),
+    }
+}
+
+// finds the index of the next non-skipped variant after `from`, wrapping
+// around to the start when `cyclic` is set; `None` means "no live variant
+// follows" (the non-cyclic chain has ended)
+fn next_target(variant_count: usize, skip: &[bool], from: usize, cyclic: bool) -> Option<usize> {
+    // when cyclic, step all the way around (including back to `from` itself)
+    // so a variant with no other live variant still wraps to itself
+    let steps = if cyclic {
+        variant_count
+    } else {
+        variant_count - from - 1
+    };
+    (1..=steps)
+        .map(|step| (from + step) % variant_count)
+        .find(|idx| !skip[*idx])
+}
+
+// mirrors `next_target`, walking backward instead of forward
+fn previous_target(
+    variant_count: usize,
+    skip: &[bool],
+    from: usize,
+    cyclic: bool,
+) -> Option<usize> {
+    let steps = if cyclic { variant_count } else { from };
+    (1..=steps)
+        .map(|step| (from + variant_count - step) % variant_count)
+        .find(|idx| !skip[*idx])
+}
+
+// builds the `match &self { Enum::A => Some(Enum::B), ... }` expression for a
+// set of per-variant targets, shared by the `next()`/`previous()` generators
+fn gen_match_expr_for_targets(
+    enum_ident: &syn::Ident,
+    variants: &[&syn::Variant],
+    targets: &[Option<usize>],
+) -> syn::ExprMatch {
+    // match on &self
+    let self_ident = ast::Ident::new("self");
+    let self_expr = ast::Expr::path(Path::with_ident(self_ident));
+    let match_field = ast::Expr::reference(self_expr);
+
+    let arms = variants
+        .iter()
+        .enumerate()
+        .map(|(i, variant)| {
+            // Enum::A => Some(Enum::B) for the target live variant, if any
+            // Enum::X => None if no live variant is reachable
+            let left = &variant.ident;
+            let right = targets[i].map(|j| &variants[j].ident);
+            gen_match_arm(enum_ident, left, right)
+        })
+        .collect::<Vec<_>>();
+
+    syn::ExprMatch {
+        attrs: Vec::new(),
+        match_token: syn::Token![match](Span::call_site()),
+        expr: Box::new(match_field.into()),
+        brace_token: syn::token::Brace(Span::call_site()),
+        arms,
+    }
+}
+
+fn gen_next_match_expr(
+    enum_ident: &syn::Ident,
+    variants: &[&syn::Variant],
+    skip: &[bool],
+    cyclic: bool,
+) -> syn::ExprMatch {
+    let variant_count = variants.len();
+    let targets = (0..variant_count)
+        .map(|i| next_target(variant_count, skip, i, cyclic))
+        .collect::<Vec<_>>();
+    gen_match_expr_for_targets(enum_ident, variants, &targets)
+}
+
+fn gen_previous_match_expr(
+    enum_ident: &syn::Ident,
+    variants: &[&syn::Variant],
+    skip: &[bool],
+    cyclic: bool,
+) -> syn::ExprMatch {
+    let variant_count = variants.len();
+    let targets = (0..variant_count)
+        .map(|i| previous_target(variant_count, skip, i, cyclic))
+        .collect::<Vec<_>>();
+    gen_match_expr_for_targets(enum_ident, variants, &targets)
+}
+
+// validates that the derive input is an enum, anchoring the error on the
+// enum's own identifier so the diagnostic points at `enum Foo` / `struct Foo`
+fn validate_enum(ast: &syn::DeriveInput) -> syn::Result<&syn::DataEnum> {
+    match &ast.data {
+        syn::Data::Enum(enum_ref) => Ok(enum_ref),
+        _ => Err(syn::Error::new_spanned(
+            &ast.ident,
+            "CaseIterable can only be derived for enums",
+        )),
+    }
+}
+
+// validates that every variant is a unit variant, accumulating one error per
+// offending variant so rustc highlights all of them in a single pass
+fn validate_variants(variants: &[&syn::Variant]) -> syn::Result<()> {
+    let mut result: syn::Result<()> = Ok(());
+    for variant in variants {
+        if !matches!(variant.fields, syn::Fields::Unit) {
+            let err = syn::Error::new_spanned(variant, "CaseIterable only supports unit variants");
+            match &mut result {
+                Ok(()) => result = Err(err),
+                Err(errors) => errors.combine(err),
+            }
+        }
+    }
+    result
+}
+
+// reads a variant's explicit discriminant, if any; only integer literals are
+// supported since arbitrary constant expressions can't be evaluated here
+fn variant_discriminant(variant: &syn::Variant) -> syn::Result<Option<isize>> {
+    let Some((_, expr)) = &variant.discriminant else {
+        return Ok(None);
+    };
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(lit),
+            ..
+        }) => lit.base10_parse::<isize>().map(Some),
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            expr,
+            ..
+        }) => match expr.as_ref() {
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Int(lit),
+                ..
+            }) => lit.base10_parse::<isize>().map(|value| Some(-value)),
+            _ => Err(syn::Error::new_spanned(
+                expr,
+                "CaseIterable only supports integer literal discriminants",
+            )),
+        },
+        _ => Err(syn::Error::new_spanned(
+            expr,
+            "CaseIterable only supports integer literal discriminants",
+        )),
+    }
+}
+
+// computes each variant's discriminant in declaration order, filling in the
+// implicit `previous + 1` value (starting at 0) the way rustc does
+fn compute_discriminants(variants: &[&syn::Variant]) -> syn::Result<Vec<isize>> {
+    let mut discriminants = Vec::with_capacity(variants.len());
+    let mut next_value: isize = 0;
+    for variant in variants {
+        let value = variant_discriminant(variant)?.unwrap_or(next_value);
+        discriminants.push(value);
+        next_value = value + 1;
+    }
+    Ok(discriminants)
+}
+
+// validates that no two variants share a discriminant, accumulating one
+// error per colliding variant
+fn validate_discriminants(variants: &[&syn::Variant], discriminants: &[isize]) -> syn::Result<()> {
+    let mut result: syn::Result<()> = Ok(());
+    for (i, variant) in variants.iter().enumerate() {
+        if discriminants[..i].contains(&discriminants[i]) {
+            let err = syn::Error::new_spanned(
+                variant,
+                format!("duplicate CaseIterable discriminant `{}`", discriminants[i]),
+            );
+            match &mut result {
+                Ok(()) => result = Err(err),
+                Err(errors) => errors.combine(err),
+            }
+        }
+    }
+    result
+}
+
+#[proc_macro_derive(CaseIterable, attributes(case_iterable))]
+pub fn derive(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as syn::DeriveInput);
+    expand(ast)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+fn expand(ast: syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let enum_name = &ast.ident;
+    let enum_type = ast::Type::path_from_ident(enum_name.into());
+    let enum_option_type: syn::Type = enum_type.into_option().into();
+    let iter_name: syn::Ident = ast::Ident::new(&format!("{}Iterator", enum_name)).into();
+    let case_at_name: syn::Ident = ast::Ident::new("case_at").into();
+
+    // generate the <Enum>Iterator struct definition
+    let iter_struct = gen_iter_struct(&ast);
+    // select and validate enum variants
+    let enum_ref = validate_enum(&ast)?;
+    let fields = enum_ref.variants.iter().collect::<Vec<_>>();
+    validate_variants(&fields)?;
+
+    // read container- and variant-level `#[case_iterable(...)]` attributes
+    let container_attrs = attr::ContainerAttrs::parse(&ast.attrs)?;
+    let skip = fields
+        .iter()
+        .map(|variant| attr::VariantAttrs::parse(&variant.attrs).map(|attrs| attrs.skip))
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let live_idents = fields
+        .iter()
+        .zip(skip.iter())
+        .filter(|(_, skip)| !**skip)
+        .map(|(variant, _)| &variant.ident)
+        .collect::<Vec<_>>();
+    if live_idents.is_empty() {
+        return Err(syn::Error::new_spanned(
+            enum_name,
+            "CaseIterable requires at least one non-skipped variant",
+        ));
+    }
+    let live_count = live_idents.len();
+
+    // arms for `case_at`, indexing into the declaration-order case list
+    let case_at_arms = live_idents
+        .iter()
+        .enumerate()
+        .map(|(i, ident)| quote! { #i => #enum_name::#ident, })
+        .collect::<Vec<_>>();
+
+    // capture and validate each variant's discriminant, explicit or implicit
+    let discriminants = compute_discriminants(&fields)?;
+    validate_discriminants(&fields, &discriminants)?;
+    let from_discriminant_arms = fields
+        .iter()
+        .zip(discriminants.iter())
+        .map(|(variant, value)| {
+            let ident = &variant.ident;
+            quote! { #value => Some(#enum_name::#ident), }
+        })
+        .collect::<Vec<_>>();
+
+    // arms for `discriminant`, matching on `&self` so no `Copy` bound is required
+    let discriminant_arms = fields
+        .iter()
+        .zip(discriminants.iter())
+        .map(|(variant, value)| {
+            let ident = &variant.ident;
+            quote! { #enum_name::#ident => #value, }
+        })
+        .collect::<Vec<_>>();
+
+    // and generate the match expressions used to step forward/backward
+    let cyclic = container_attrs.cyclic;
+    let next_match_expr = gen_next_match_expr(enum_name, &fields, &skip, cyclic);
+    let previous_match_expr = gen_previous_match_expr(enum_name, &fields, &skip, cyclic);
+
+    let next_name: syn::Ident = container_attrs
+        .rename_next
+        .unwrap_or_else(|| ast::Ident::new("next").into());
+    let all_cases_name: syn::Ident = container_attrs
+        .rename_all_cases
+        .unwrap_or_else(|| ast::Ident::new("all_cases").into());
+    let all_cases_doc = if container_attrs.cyclic {
+        quote! {
+            #[doc = "Produces one full cycle over every non-skipped case; `next()` wraps past \
+                     the final case, but this iterator stops after visiting each live case once."]
+        }
+    } else {
+        quote! {}
+    };
+
+    // produce macro output token stream
+    let tokens = quote! {
+        impl #enum_name {
+            pub fn #next_name(&self) -> #enum_option_type {
+                <#enum_name as case_iterable::CaseIterable>::next_case(self)
+            }
+
+            /// Returns the case that precedes this one, or `None` if this is the first case.
+            pub fn previous(&self) -> #enum_option_type {
+                #previous_match_expr
+            }
+
+            #all_cases_doc
+            pub fn #all_cases_name() -> #iter_name {
+                <#enum_name as case_iterable::CaseIterable>::all_cases()
+            }
+
+            /// Returns the case at `index` in the generated case list, or `None` if
+            /// `index` is out of range.
+            pub fn case(index: usize) -> #enum_option_type {
+                if index < #live_count {
+                    Some(Self::#case_at_name(index))
+                } else {
+                    None
+                }
+            }
+
+            /// Returns this case's discriminant value.
+            pub fn discriminant(&self) -> isize {
+                match self {
+                    #(#discriminant_arms)*
+                }
+            }
+
+            /// Returns the case whose discriminant is `value`, or `None` if no case
+            /// has that discriminant.
+            pub fn from_discriminant(value: isize) -> #enum_option_type {
+                match value {
+                    #(#from_discriminant_arms)*
+                    _ => None,
+                }
+            }
+
+            fn #case_at_name(index: usize) -> #enum_name {
+                match index {
+                    #(#case_at_arms)*
+                    _ => unreachable!("case_at index out of bounds"),
+                }
+            }
+        }
+
+        impl case_iterable::CaseIterable for #enum_name {
+            type Iter = #iter_name;
+
+            fn all_cases() -> Self::Iter {
+                #iter_name::new()
+            }
+
+            fn next_case(&self) -> #enum_option_type {
+                #next_match_expr
+            }
+        }
+
+        #iter_struct
+
+        impl #iter_name {
+            fn new() -> Self {
+                Self { front: 0, back: #live_count }
+            }
+        }
+
+        impl Iterator for #iter_name {
+            type Item = #enum_name;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                if self.front >= self.back {
+                    return None;
+                }
+                let item = #enum_name::#case_at_name(self.front);
+                self.front += 1;
+                Some(item)
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                let len = self.len();
+                (len, Some(len))
+            }
+        }
+
+        impl ExactSizeIterator for #iter_name {
+            fn len(&self) -> usize {
+                self.back - self.front
+            }
+        }
+
+        impl DoubleEndedIterator for #iter_name {
+            fn next_back(&mut self) -> Option<Self::Item> {
+                if self.front >= self.back {
+                    return None;
+                }
+                self.back -= 1;
+                Some(#enum_name::#case_at_name(self.back))
+            }
+        }
+    };
+    Ok(tokens)
+}
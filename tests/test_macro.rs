@@ -23,3 +23,126 @@ fn test_expand() {
     assert_eq!(cases[1], TestEnum::B);
     assert_eq!(cases[2], TestEnum::C);
 }
+
+#[test]
+fn test_skip() {
+    #[derive(CaseIterable, Debug, PartialEq)]
+    enum TestEnum {
+        A,
+        #[case_iterable(skip)]
+        B,
+        C,
+    }
+
+    assert_eq!(TestEnum::A.next(), Some(TestEnum::C));
+    assert_eq!(TestEnum::B.next(), Some(TestEnum::C));
+    assert_eq!(TestEnum::C.next(), None);
+
+    let cases = TestEnum::all_cases().collect::<Vec<_>>();
+    assert_eq!(cases, vec![TestEnum::A, TestEnum::C]);
+}
+
+#[test]
+fn test_cyclic() {
+    #[derive(CaseIterable, Debug, PartialEq)]
+    #[case_iterable(cyclic)]
+    enum TestEnum {
+        A,
+        B,
+        C,
+    }
+
+    assert_eq!(TestEnum::A.next(), Some(TestEnum::B));
+    assert_eq!(TestEnum::B.next(), Some(TestEnum::C));
+    assert_eq!(TestEnum::C.next(), Some(TestEnum::A));
+
+    // all_cases() still produces exactly one cycle, despite next() wrapping
+    let cases = TestEnum::all_cases().collect::<Vec<_>>();
+    assert_eq!(cases, vec![TestEnum::A, TestEnum::B, TestEnum::C]);
+}
+
+#[test]
+fn test_rename() {
+    #[derive(CaseIterable, Debug, PartialEq)]
+    #[case_iterable(rename_all_cases = "variants", rename_next = "succ")]
+    enum TestEnum {
+        A,
+        B,
+        C,
+    }
+
+    assert_eq!(TestEnum::A.succ(), Some(TestEnum::B));
+
+    let cases = TestEnum::variants().collect::<Vec<_>>();
+    assert_eq!(cases, vec![TestEnum::A, TestEnum::B, TestEnum::C]);
+}
+
+#[test]
+fn test_previous() {
+    #[derive(CaseIterable, Debug, PartialEq)]
+    enum TestEnum {
+        A,
+        B,
+        C,
+    }
+
+    assert_eq!(TestEnum::A.previous(), None);
+    assert_eq!(TestEnum::B.previous(), Some(TestEnum::A));
+    assert_eq!(TestEnum::C.previous(), Some(TestEnum::B));
+}
+
+#[test]
+fn test_iterator_exact_size_and_double_ended() {
+    #[derive(CaseIterable, Debug, PartialEq)]
+    enum TestEnum {
+        A,
+        B,
+        C,
+    }
+
+    let mut iter = TestEnum::all_cases();
+    assert_eq!(iter.len(), 3);
+    assert_eq!(iter.next(), Some(TestEnum::A));
+    assert_eq!(iter.len(), 2);
+    assert_eq!(iter.next_back(), Some(TestEnum::C));
+    assert_eq!(iter.len(), 1);
+    assert_eq!(iter.next(), Some(TestEnum::B));
+    assert_eq!(iter.next(), None);
+
+    let rev_cases = TestEnum::all_cases().rev().collect::<Vec<_>>();
+    assert_eq!(rev_cases, vec![TestEnum::C, TestEnum::B, TestEnum::A]);
+}
+
+#[test]
+fn test_case() {
+    #[derive(CaseIterable, Debug, PartialEq)]
+    enum TestEnum {
+        A,
+        B,
+        C,
+    }
+
+    assert_eq!(TestEnum::case(0), Some(TestEnum::A));
+    assert_eq!(TestEnum::case(1), Some(TestEnum::B));
+    assert_eq!(TestEnum::case(2), Some(TestEnum::C));
+    assert_eq!(TestEnum::case(3), None);
+}
+
+#[test]
+fn test_discriminant_roundtrip() {
+    #[derive(CaseIterable, Debug, PartialEq)]
+    enum TestEnum {
+        A = 10,
+        B,
+        C = 20,
+    }
+
+    assert_eq!(TestEnum::A.discriminant(), 10);
+    assert_eq!(TestEnum::B.discriminant(), 11);
+    assert_eq!(TestEnum::C.discriminant(), 20);
+
+    assert_eq!(TestEnum::from_discriminant(10), Some(TestEnum::A));
+    assert_eq!(TestEnum::from_discriminant(11), Some(TestEnum::B));
+    assert_eq!(TestEnum::from_discriminant(20), Some(TestEnum::C));
+    assert_eq!(TestEnum::from_discriminant(0), None);
+}